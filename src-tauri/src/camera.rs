@@ -0,0 +1,245 @@
+//! Backend de capture pour appareils photo tethered (DSLR / hybrides) via libgphoto2.
+//!
+//! Le backend webcam historique (`nokhwa`) reste le chemin par defaut ; ce module ajoute
+//! un second backend pour les appareils connectes en USB, capables d'une bien meilleure
+//! resolution. On expose l'autodetection, l'apercu en direct (`capture_preview`), le
+//! declenchement pleine resolution avec telechargement direct en memoire (pas d'aller-retour
+//! carte SD) et une lecture/ecriture generique d'un sous-ensemble de reglages (ISO, ouverture,
+//! vitesse, balance des blancs) en parcourant l'arbre de widgets de configuration.
+
+use std::sync::{Mutex, OnceLock};
+
+use gphoto2::widget::WidgetValue;
+use gphoto2::Context;
+use serde::Serialize;
+
+/// Reglages exposes a l'UI. On reste volontairement sur un sous-ensemble courant afin que
+/// le frontend puisse construire des controles generiques sans connaitre chaque modele.
+const EXPOSED_CONFIG_KEYS: &[&str] = &["iso", "aperture", "shutterspeed", "whitebalance"];
+
+/// Image capturee renvoyee a React : le buffer brut tel que fourni par l'appareil, plus
+/// l'extension/mime devinee a partir du nom de fichier cote appareil pour l'affichage.
+#[derive(Serialize)]
+pub struct CapturedImage {
+    pub file_name: String,
+    pub mime: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Frame d'apercu en direct, volontairement legere : on renvoie le JPEG brut du live view.
+#[derive(Serialize)]
+pub struct PreviewFrame {
+    pub mime: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Entree de configuration normalisee pour l'UI : nom technique, libelle lisible, valeur
+/// courante et choix possibles lorsque le widget est un menu/radio.
+#[derive(Serialize)]
+pub struct CameraConfigEntry {
+    pub name: String,
+    pub label: String,
+    pub value: Option<String>,
+    pub choices: Vec<String>,
+}
+
+/// Appareil ouvert et son contexte, conserves entre les appels. Reouvrir le peripherique USB
+/// (enumeration du bus + claim) a chaque frame rendrait le live view inutilisable ; on garde donc
+/// une poignee persistante, a l'image des etats globaux de `session`/`profiling`.
+struct CameraHandle {
+    context: Context,
+    camera: gphoto2::Camera,
+}
+
+static CAMERA: OnceLock<Mutex<Option<CameraHandle>>> = OnceLock::new();
+
+fn camera_cell() -> &'static Mutex<Option<CameraHandle>> {
+    CAMERA.get_or_init(|| Mutex::new(None))
+}
+
+/// Execute `f` avec la poignee persistante, en ouvrant l'appareil au premier appel. En cas
+/// d'erreur (debranchement, conflit de claim), on relache la poignee pour forcer une
+/// reouverture propre au prochain appel.
+fn with_camera<T>(f: impl FnOnce(&CameraHandle) -> anyhow::Result<T>) -> anyhow::Result<T> {
+    let mut guard = match camera_cell().lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+
+    if guard.is_none() {
+        let context = Context::new()?;
+        let camera = context.autodetect_camera().wait()?;
+        *guard = Some(CameraHandle { context, camera });
+    }
+
+    let handle = guard.as_ref().expect("handle initialise ci-dessus");
+    match f(handle) {
+        Ok(value) => Ok(value),
+        Err(err) => {
+            // La poignee est peut-etre corrompue : on la jette pour repartir de zero.
+            *guard = None;
+            Err(err)
+        }
+    }
+}
+
+/// Indique si au moins un appareil photo tethered est present. Comme pour la webcam,
+/// l'appel ne reserve pas l'appareil : on peut l'utiliser au demarrage pour exposer un
+/// etat rapide au frontend.
+pub fn detect_camera_presence() -> anyhow::Result<bool> {
+    let context = Context::new()?;
+    let cameras = context.list_cameras().wait()?;
+    Ok(!cameras.is_empty())
+}
+
+/// Devine le type MIME a partir de l'extension du nom de fichier cote appareil.
+/// Les appareils nomment leurs captures `IMG_xxxx.JPG`/`.CR2`/`.NEF`, etc.
+fn guess_mime(file_name: &str) -> String {
+    let ext = file_name
+        .rsplit('.')
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match ext.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "cr2" | "cr3" | "nef" | "arw" | "dng" | "raf" | "orf" => "image/x-raw",
+        "heic" | "heif" => "image/heif",
+        _ => "application/octet-stream",
+    }
+    .to_string()
+}
+
+/// Recupere une frame d'apercu (live view) pour permettre au frontend d'afficher un cadrage
+/// en temps reel avant le declenchement.
+pub fn capture_preview() -> anyhow::Result<PreviewFrame> {
+    with_camera(|handle| {
+        let preview = handle.camera.capture_preview().wait()?;
+        let data = preview.get_data(&handle.context).wait()?;
+        Ok(PreviewFrame {
+            mime: "image/jpeg".to_string(),
+            bytes: data.to_vec(),
+        })
+    })
+}
+
+/// Declenche une capture pleine resolution et telecharge le fichier directement en memoire.
+/// On ne passe pas par la carte SD : le fichier est lu depuis le systeme de fichiers de
+/// l'appareil puis renvoye sous forme de buffer.
+pub fn capture_frame() -> anyhow::Result<CapturedImage> {
+    with_camera(|handle| {
+        let camera = &handle.camera;
+
+        // Forcer la cible de capture sur la RAM interne pour eviter tout aller-retour carte SD :
+        // l'image n'est ecrite que dans le buffer de l'appareil avant d'etre telechargee en memoire.
+        if let Ok(target) = camera.config_key("capturetarget").wait() {
+            if let Err(err) = target.set_value(WidgetValue::Menu {
+                value: "Internal RAM".to_string(),
+                choices: Vec::new(),
+            }) {
+                log::warn!("capture_frame: capturetarget non positionnable: {err}");
+            } else if let Err(err) = camera.set_config(&target).wait() {
+                log::warn!("capture_frame: capturetarget non applicable: {err}");
+            }
+        } else {
+            log::debug!("capture_frame: widget capturetarget absent, cible par defaut conservee");
+        }
+
+        let path = camera.capture_image().wait()?;
+        let file = camera
+            .fs()
+            .download(&path.folder(), &path.name())
+            .wait()?;
+        let data = file.get_data(&handle.context).wait()?;
+        let file_name = path.name().to_string();
+        let mime = guess_mime(&file_name);
+
+        log::info!(
+            "capture_frame: {} telecharge en memoire ({} octets)",
+            file_name,
+            data.len()
+        );
+
+        Ok(CapturedImage {
+            file_name,
+            mime,
+            bytes: data.to_vec(),
+        })
+    })
+}
+
+/// Parcourt l'arbre de widgets de configuration et renvoie le sous-ensemble de reglages
+/// exposes a l'UI. Le parcours est iteratif pour eviter un depassement de pile sur des
+/// arbres profonds, a l'image de `compute_dir_size`.
+pub fn read_config() -> anyhow::Result<Vec<CameraConfigEntry>> {
+    with_camera(|handle| {
+        let root = handle.camera.config().wait()?;
+
+        let mut entries = Vec::new();
+        let mut stack = vec![root];
+
+        while let Some(widget) = stack.pop() {
+            for child in widget.children_iter() {
+                // Les sections regroupent d'autres widgets : on descend dedans.
+                if child.children_iter().next().is_some() {
+                    stack.push(child);
+                    continue;
+                }
+
+                let name = child.name().to_string();
+                if !EXPOSED_CONFIG_KEYS.contains(&name.as_str()) {
+                    continue;
+                }
+
+                let (value, choices) = match child.value() {
+                    Ok(WidgetValue::Menu { value, choices }) => (Some(value), choices),
+                    Ok(WidgetValue::Text(value)) => (Some(value), Vec::new()),
+                    Ok(WidgetValue::Toggle(on)) => (Some(on.to_string()), Vec::new()),
+                    _ => (None, Vec::new()),
+                };
+
+                entries.push(CameraConfigEntry {
+                    name,
+                    label: child.label().to_string(),
+                    value,
+                    choices,
+                });
+            }
+        }
+
+        Ok(entries)
+    })
+}
+
+/// Positionne la valeur d'un reglage de configuration par son nom technique. Seules les
+/// cles exposees sont acceptees pour eviter d'ecrire dans des widgets non prevus par l'UI.
+pub fn set_config(name: &str, value: &str) -> anyhow::Result<()> {
+    if !EXPOSED_CONFIG_KEYS.contains(&name) {
+        anyhow::bail!("reglage non expose: {name}");
+    }
+
+    with_camera(|handle| {
+        let widget = handle.camera.config_key(name).wait()?;
+
+        // Construire une valeur du meme type que le widget cible : un menu reste un menu, un champ
+        // texte un texte, une bascule une bascule. Sinon on risque de mal positionner ou d'echouer.
+        let new_value = match widget.value()? {
+            WidgetValue::Menu { choices, .. } => WidgetValue::Menu {
+                value: value.to_string(),
+                choices,
+            },
+            WidgetValue::Text(_) => WidgetValue::Text(value.to_string()),
+            WidgetValue::Toggle(_) => WidgetValue::Toggle(matches!(
+                value.to_ascii_lowercase().as_str(),
+                "1" | "true" | "on" | "yes"
+            )),
+            other => anyhow::bail!("type de widget non supporte pour {name}: {other:?}"),
+        };
+
+        widget.set_value(new_value)?;
+        handle.camera.set_config(&widget).wait()?;
+
+        log::info!("set_config: {name} => {value}");
+        Ok(())
+    })
+}
@@ -0,0 +1,153 @@
+//! Couche de decodage image et pipeline couleur pour les imports et `export_pdf`.
+//!
+//! Les sources peuvent etre des JPEG/PNG classiques mais aussi des RAW (CR2/NEF/DNG) ou du
+//! HEIF quand la capture vient d'un appareil photo. On detecte le format d'entree et on route :
+//!   - RAW  -> `rawloader` + `imagepipe` (dematricage -> balance des blancs -> sRGB) ;
+//!   - HEIF -> `libheif-rs` ;
+//!   - reste -> crate `image` (PNG/JPEG).
+//! Toutes les branches produisent un `DynamicImage` commun pour la suite du pipeline.
+//!
+//! Les decodeurs natifs (RAW/HEIF) sont derriere des features Cargo afin que les builds sans les
+//! bibliotheques systeme compilent malgre tout.
+
+use std::path::Path;
+
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+
+/// Pipeline couleur/tonalite applique avant l'assemblage des pages. Selectionne par le profil
+/// actif via `AppConfig`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColorPipeline {
+    /// Niveaux de gris simples.
+    Grayscale,
+    /// Rendu "document" : niveaux de gris a fort contraste pour texte sur fond clair.
+    Document,
+    /// Couleur pleine, aucune transformation tonale.
+    #[default]
+    FullColor,
+}
+
+impl ColorPipeline {
+    /// Applique la transformation tonale a l'image decodee.
+    pub fn apply(self, image: DynamicImage) -> DynamicImage {
+        match self {
+            ColorPipeline::Grayscale => DynamicImage::ImageLuma8(image.to_luma8()),
+            ColorPipeline::Document => {
+                // Niveaux de gris puis boost de contraste pour detacher le texte du fond.
+                DynamicImage::ImageLuma8(image.to_luma8()).adjust_contrast(40.0)
+            }
+            ColorPipeline::FullColor => image,
+        }
+    }
+}
+
+/// Indique si l'extension de `path` est acceptee par le profil, a la maniere des filtres
+/// "allowed/excluded" de czkawka : une extension exclue est toujours rejetee ; si une liste
+/// d'extensions autorisees est fournie, seules celles-ci passent.
+pub fn extension_allowed(path: &Path, allowed: &[String], excluded: &[String]) -> bool {
+    let ext = match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) => ext.to_ascii_lowercase(),
+        None => return allowed.is_empty(),
+    };
+
+    if excluded.iter().any(|e| e.eq_ignore_ascii_case(&ext)) {
+        return false;
+    }
+    if allowed.is_empty() {
+        return true;
+    }
+    allowed.iter().any(|e| e.eq_ignore_ascii_case(&ext))
+}
+
+/// Formats d'entree reconnus, routant vers le decodeur approprie.
+enum InputFormat {
+    Raw,
+    Heif,
+    Standard,
+}
+
+fn classify(path: &Path) -> InputFormat {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match ext.as_str() {
+        "cr2" | "cr3" | "nef" | "dng" | "arw" | "raf" | "orf" | "rw2" => InputFormat::Raw,
+        "heic" | "heif" => InputFormat::Heif,
+        _ => InputFormat::Standard,
+    }
+}
+
+/// Decode un fichier image vers un `DynamicImage` commun, quel que soit le format d'entree.
+pub fn decode_image(path: &Path) -> anyhow::Result<DynamicImage> {
+    match classify(path) {
+        InputFormat::Raw => decode_raw(path),
+        InputFormat::Heif => decode_heif(path),
+        InputFormat::Standard => Ok(image::open(path)?),
+    }
+}
+
+/// Decode puis applique le pipeline couleur. C'est le point d'entree utilise par `export_pdf`
+/// et les imports pour obtenir un buffer pret a assembler.
+pub fn decode_and_process(path: &Path, pipeline: ColorPipeline) -> anyhow::Result<DynamicImage> {
+    Ok(pipeline.apply(decode_image(path)?))
+}
+
+#[cfg(feature = "raw")]
+fn decode_raw(path: &Path) -> anyhow::Result<DynamicImage> {
+    // imagepipe enchaine dematricage -> balance des blancs -> conversion sRGB.
+    let mut pipeline = imagepipe::Pipeline::new_from_file(path)
+        .map_err(|err| anyhow::anyhow!("ouverture RAW impossible: {err}"))?;
+    let decoded = pipeline
+        .output_8bit(None)
+        .map_err(|err| anyhow::anyhow!("dematricage RAW impossible: {err}"))?;
+
+    let buffer =
+        image::RgbImage::from_raw(decoded.width as u32, decoded.height as u32, decoded.data)
+            .ok_or_else(|| anyhow::anyhow!("buffer RAW de taille incoherente"))?;
+    Ok(DynamicImage::ImageRgb8(buffer))
+}
+
+#[cfg(not(feature = "raw"))]
+fn decode_raw(_path: &Path) -> anyhow::Result<DynamicImage> {
+    anyhow::bail!("support RAW non compile (activer la feature `raw`)")
+}
+
+#[cfg(feature = "heif")]
+fn decode_heif(path: &Path) -> anyhow::Result<DynamicImage> {
+    use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+    let lib = LibHeif::new();
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| anyhow::anyhow!("chemin HEIF non UTF-8"))?;
+    let ctx = HeifContext::read_from_file(path_str)?;
+    let handle = ctx.primary_image_handle()?;
+    let decoded = lib.decode(&handle, ColorSpace::Rgb(RgbChroma::Rgb), None)?;
+
+    let width = decoded.width();
+    let height = decoded.height();
+    let plane = decoded
+        .planes()
+        .interleaved
+        .ok_or_else(|| anyhow::anyhow!("plan HEIF entrelace absent"))?;
+
+    // Le stride peut inclure du padding en fin de ligne : on recopie ligne par ligne.
+    let mut rgb = Vec::with_capacity((width * height * 3) as usize);
+    for row in 0..height as usize {
+        let start = row * plane.stride;
+        rgb.extend_from_slice(&plane.data[start..start + (width as usize * 3)]);
+    }
+
+    let buffer = image::RgbImage::from_raw(width, height, rgb)
+        .ok_or_else(|| anyhow::anyhow!("buffer HEIF de taille incoherente"))?;
+    Ok(DynamicImage::ImageRgb8(buffer))
+}
+
+#[cfg(not(feature = "heif"))]
+fn decode_heif(_path: &Path) -> anyhow::Result<DynamicImage> {
+    anyhow::bail!("support HEIF non compile (activer la feature `heif`)")
+}
@@ -1,5 +1,11 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod camera;
+mod imaging;
+mod profiling;
+mod session;
+mod shell_open;
+
 use nokhwa::utils::ApiBackend;
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -24,17 +30,9 @@ fn log_path() -> Result<String, String> {
         })
 }
 
-#[tauri::command]
-fn capture_frame_stub() -> Result<(), String> {
-    log::info!("capture_frame:start");
-    log::debug!(
-        "capture_frame: TODO: ouvrir la webcam selectionnee et retourner un buffer d'image"
-    );
-    Err("capture_frame not implemented".to_string())
-}
-
 #[tauri::command]
 fn detect_document_stub() -> Result<(), String> {
+    let _span = profiling::guard("detect_document");
     log::info!("detect_document:start");
     log::debug!("detect_document: TODO: analyser l'image, retourner coins + score de confiance");
     Err("detect_document not implemented".to_string())
@@ -42,7 +40,13 @@ fn detect_document_stub() -> Result<(), String> {
 
 #[tauri::command]
 fn export_pdf_stub() -> Result<(), String> {
+    let _span = profiling::guard("export_pdf");
     log::info!("export_pdf:start");
+    // Le profil actif selectionne le pipeline couleur/tonalite applique avant l'assemblage.
+    let pipeline = load_app_config()
+        .map(|config| config.color_pipeline)
+        .unwrap_or_default();
+    log::debug!("export_pdf: pipeline couleur selectionne = {pipeline:?}");
     log::debug!("export_pdf: TODO: assembler les pages (ordre, dpi, profil) et ecrire le PDF");
     Err("export_pdf not implemented".to_string())
 }
@@ -50,24 +54,253 @@ fn export_pdf_stub() -> Result<(), String> {
 #[derive(Serialize)]
 struct RuntimeInfo {
     webcam_detected: bool,
+    camera_detected: bool,
     active_profile: Option<String>,
 }
 
+/// Declenche une capture pleine resolution sur l'appareil tethered (DSLR / hybride) et
+/// renvoie le buffer image directement a React, sans aller-retour carte SD.
+#[tauri::command]
+fn capture_frame() -> Result<camera::CapturedImage, String> {
+    let _span = profiling::guard("capture_frame");
+    log::info!("capture_frame:start");
+    camera::capture_frame().map_err(|err| {
+        log::error!("capture_frame:failed: {err}");
+        err.to_string()
+    })
+}
+
+/// Renvoie une frame d'apercu (live view) pour afficher un cadrage en direct avant capture.
+#[tauri::command]
+fn camera_preview() -> Result<camera::PreviewFrame, String> {
+    camera::capture_preview().map_err(|err| {
+        log::error!("camera_preview:failed: {err}");
+        err.to_string()
+    })
+}
+
+/// Lit le sous-ensemble de reglages exposes (ISO, ouverture, vitesse, balance des blancs)
+/// pour que l'UI construise des controles generiques.
+#[tauri::command]
+fn camera_read_config() -> Result<Vec<camera::CameraConfigEntry>, String> {
+    camera::read_config().map_err(|err| {
+        log::error!("camera_read_config:failed: {err}");
+        err.to_string()
+    })
+}
+
+/// Positionne un reglage de configuration de l'appareil par son nom technique.
+#[tauri::command]
+fn camera_set_config(name: String, value: String) -> Result<(), String> {
+    camera::set_config(&name, &value).map_err(|err| {
+        log::error!("camera_set_config:failed: {err}");
+        err.to_string()
+    })
+}
+
+/// Ouvre le fichier exporte avec le handler par defaut de l'OS.
+#[tauri::command]
+fn open_exported_file(path: String) -> Result<(), String> {
+    shell_open::open_exported_file(Path::new(&path)).map_err(|err| {
+        log::error!("open_exported_file:failed: {err}");
+        err.to_string()
+    })
+}
+
+/// Revele le fichier exporte dans le gestionnaire de fichiers (selection si possible).
+#[tauri::command]
+fn reveal_in_file_manager(path: String) -> Result<(), String> {
+    shell_open::reveal_in_file_manager(Path::new(&path)).map_err(|err| {
+        log::error!("reveal_in_file_manager:failed: {err}");
+        err.to_string()
+    })
+}
+
+/// Empaquetage detecte pour Photon, afin que le frontend adapte son comportement.
+#[derive(Serialize)]
+struct SandboxInfo {
+    flatpak: bool,
+    snap: bool,
+    appimage: bool,
+}
+
+/// Renvoie l'empaquetage dans lequel Photon s'execute (Flatpak/Snap/AppImage).
+#[tauri::command]
+fn sandbox_info() -> SandboxInfo {
+    SandboxInfo {
+        flatpak: shell_open::running_in_flatpak(),
+        snap: shell_open::running_in_snap(),
+        appimage: shell_open::running_in_appimage(),
+    }
+}
+
+/// Demarre une session de numerisation multi-pages et renvoie son identifiant.
+#[tauri::command]
+fn start_scan_session() -> Result<String, String> {
+    let workspace = temporary_workspace_dir().map_err(|err| {
+        log::error!("start_scan_session:workspace_failed: {err}");
+        err.to_string()
+    })?;
+    session::start_session(&workspace).map_err(|err| {
+        log::error!("start_scan_session:failed: {err}");
+        err.to_string()
+    })
+}
+
+/// Ajoute une page (buffer capture/importe) a la session et renvoie son id de page.
+#[tauri::command]
+fn append_scan_page(
+    session_id: String,
+    bytes: Vec<u8>,
+    extension: String,
+) -> Result<String, String> {
+    // Filtre d'extensions du profil actif : on rejette les fichiers que le profil n'accepte pas,
+    // a la maniere des filtres allowed/excluded de czkawka, avant tout deversement sur disque.
+    let config = load_app_config().map_err(|err| {
+        log::error!("append_scan_page:config_load_failed: {err}");
+        err.to_string()
+    })?;
+    let candidate = PathBuf::from(format!("page.{}", extension.trim_start_matches('.')));
+    if !imaging::extension_allowed(
+        &candidate,
+        &config.allowed_extensions,
+        &config.excluded_extensions,
+    ) {
+        log::info!("append_scan_page: extension refusee par le profil: {extension}");
+        return Err(format!("extension non acceptee par le profil: {extension}"));
+    }
+
+    session::append_page(&session_id, &bytes, &extension).map_err(|err| {
+        log::error!("append_scan_page:failed: {err}");
+        err.to_string()
+    })
+}
+
+/// Reordonne les pages de la session selon la liste d'ids fournie.
+#[tauri::command]
+fn reorder_scan_pages(session_id: String, ordered_ids: Vec<String>) -> Result<(), String> {
+    session::reorder_pages(&session_id, &ordered_ids).map_err(|err| {
+        log::error!("reorder_scan_pages:failed: {err}");
+        err.to_string()
+    })
+}
+
+/// Supprime plusieurs pages de la session en un seul appel.
+#[tauri::command]
+fn delete_scan_pages(session_id: String, page_ids: Vec<String>) -> Result<(), String> {
+    session::delete_pages(&session_id, &page_ids).map_err(|err| {
+        log::error!("delete_scan_pages:failed: {err}");
+        err.to_string()
+    })
+}
+
+/// Exporte la session vers un PDF multi-pages (`pdf`) ou une archive ZIP d'images (`zip`),
+/// en appliquant le pipeline couleur du profil actif.
+#[tauri::command]
+fn export_scan_session(
+    session_id: String,
+    output_path: String,
+    format: String,
+) -> Result<(), String> {
+    let export_format = match format.to_ascii_lowercase().as_str() {
+        "pdf" => session::ExportFormat::Pdf,
+        "zip" => session::ExportFormat::Zip,
+        other => return Err(format!("format d'export inconnu: {other}")),
+    };
+    let pipeline = load_app_config()
+        .map(|config| config.color_pipeline)
+        .unwrap_or_default();
+
+    session::export_session(
+        &session_id,
+        Path::new(&output_path),
+        export_format,
+        pipeline,
+    )
+    .map_err(|err| {
+        log::error!("export_scan_session:failed: {err}");
+        err.to_string()
+    })
+}
+
+/// Ferme une session et libere son dossier de scratch.
+#[tauri::command]
+fn close_scan_session(session_id: String) -> Result<(), String> {
+    session::close_session(&session_id).map_err(|err| {
+        log::error!("close_scan_session:failed: {err}");
+        err.to_string()
+    })
+}
+
+/// Renvoie les agregats de profilage par etape et ecrit le fichier de trace JSON a cote de
+/// `photon.log` dans le dossier de donnees applicatif.
+#[tauri::command]
+fn profiling_report() -> Result<profiling::ProfilingReport, String> {
+    let trace_path = app_data_dir()
+        .map(|dir| dir.join("photon.trace.json"))
+        .map_err(|err| {
+            log::error!("profiling_report:trace_path_failed: {err}");
+            err.to_string()
+        })?;
+
+    profiling::report(trace_path).map_err(|err| {
+        log::error!("profiling_report:failed: {err}");
+        err.to_string()
+    })
+}
+
 /// Configuration applicative minimale pour exposer un profil actif.
 /// Penser à étendre cette structure lorsque d'autres préférences seront ajoutées
 /// (ex: dernier dossier utilisé, favoris de formats, etc.).
 #[derive(Serialize, Deserialize, Default)]
 struct AppConfig {
     active_profile: Option<String>,
+    /// Active le profilage local du pipeline. Desactive par defaut pour un overhead nul.
+    #[serde(default)]
+    profiling_enabled: bool,
+    /// Pipeline couleur/tonalite applique a l'export (niveaux de gris, document, couleur).
+    #[serde(default)]
+    color_pipeline: imaging::ColorPipeline,
+    /// Extensions acceptees a l'import (vide => toutes), a la maniere des filtres czkawka.
+    #[serde(default)]
+    allowed_extensions: Vec<String>,
+    /// Extensions toujours rejetees a l'import.
+    #[serde(default)]
+    excluded_extensions: Vec<String>,
+}
+
+/// Resultat d'un palier de nettoyage : combien d'entrees supprimees et octets liberes.
+#[derive(Serialize)]
+struct TierOutcome {
+    tier: String,
+    removed_entries: usize,
+    reclaimed_bytes: u64,
 }
 
 /// Rapport de nettoyage pour tracer ce qui a ete supprime dans le dossier temporaire.
+/// On conserve les totaux historiques (`cleaned_entries`/`reclaimed_bytes`) et on detaille
+/// desormais le travail par palier ainsi que le gain d'espace disque observe.
 #[derive(Default, Serialize)]
 struct CleanupReport {
     cleaned_entries: usize,
     reclaimed_bytes: u64,
+    tiers: Vec<TierOutcome>,
+    available_before_bytes: u64,
+    available_after_bytes: u64,
+    freed_space_delta_bytes: i64,
+}
+
+/// Artefact temporaire candidat au nettoyage, pre-mesure pour eviter de re-parcourir le disque.
+struct TempCandidate {
+    path: PathBuf,
+    is_dir: bool,
+    size: u64,
+    modified: SystemTime,
 }
 
+// Prefixe des dossiers de scratch de session, exploite pour le palier dedie.
+const SESSION_PREFIX: &str = "session-";
+
 /// Etat de robustesse global expose au frontend pour alerter l'utilisateur en cas de faible espace disque.
 #[derive(Serialize)]
 struct HousekeepingStatus {
@@ -76,6 +309,7 @@ struct HousekeepingStatus {
     low_space: bool,
     temp_dir: String,
     cleanup: CleanupReport,
+    sessions: Vec<session::SessionFootprint>,
 }
 
 // Espace disque minimal recommande avant capture/export pour respecter la spec (<200 Mo => alerte).
@@ -97,6 +331,14 @@ fn runtime_info() -> Result<RuntimeInfo, String> {
         }
     };
 
+    let camera_detected = match camera::detect_camera_presence() {
+        Ok(presence) => presence,
+        Err(err) => {
+            log::error!("runtime_info:camera_detection_failed: {err}");
+            false
+        }
+    };
+
     let active_profile = match load_active_profile() {
         Ok(profile) => profile,
         Err(err) => {
@@ -107,6 +349,7 @@ fn runtime_info() -> Result<RuntimeInfo, String> {
 
     Ok(RuntimeInfo {
         webcam_detected,
+        camera_detected,
         active_profile,
     })
 }
@@ -220,9 +463,14 @@ fn compute_dir_size(root: &Path) -> anyhow::Result<u64> {
     Ok(total)
 }
 
-/// Supprime les fichiers temporaires plus anciens que `TEMP_RETENTION_HOURS` afin de ne
-/// pas saturer le disque. Les erreurs sont loggees mais l'execution se poursuit pour
-/// nettoyer un maximum d'entrees.
+/// Nettoyage priorise et cible sur l'espace a liberer.
+///
+/// Lorsque l'espace disque passe sous `MIN_DISK_SPACE_BYTES`, on cible en priorite le scratch de
+/// session le plus ancien (`session-*`, hors sessions ouvertes) et on re-sonde l'espace libre
+/// apres, en s'arretant des que le seuil est repasse. Les donnees d'une session encore ouverte ne
+/// sont jamais touchees. Le balayage par age reste le palier de plus basse priorite, toujours
+/// execute, pour un comportement previsible quand l'espace est suffisant. Les erreurs sont
+/// loggees sans interrompre le nettoyage.
 fn cleanup_temporary_files() -> anyhow::Result<CleanupReport> {
     let temp_dir = temporary_workspace_dir()?;
     let cutoff = SystemTime::now()
@@ -230,8 +478,76 @@ fn cleanup_temporary_files() -> anyhow::Result<CleanupReport> {
         .ok_or_else(|| anyhow::anyhow!("system time overflow when computing cutoff"))?;
 
     let mut report = CleanupReport::default();
+    let available_before = available_disk_space(&temp_dir).unwrap_or_else(|err| {
+        log::error!("cleanup: unable to probe free space: {err}");
+        0
+    });
+    report.available_before_bytes = available_before;
+
+    // Les sessions ouvertes sont exclues d'emblee de tous les paliers.
+    let open_sessions = session::open_session_dirs();
+
+    let mut sessions: Vec<TempCandidate> = collect_temp_candidates(&temp_dir, &open_sessions)
+        .into_iter()
+        .filter(|candidate| {
+            candidate.is_dir
+                && candidate
+                    .path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().starts_with(SESSION_PREFIX))
+                    .unwrap_or(false)
+        })
+        .collect();
+
+    // Scratch de session: le plus ancien en premier.
+    sessions.sort_by_key(|c| c.modified);
+
+    let below_threshold =
+        |dir: &Path| available_disk_space(dir).map(|a| a < MIN_DISK_SPACE_BYTES).unwrap_or(false);
+
+    if below_threshold(&temp_dir) {
+        let mut outcome = TierOutcome {
+            tier: "session-scratch".to_string(),
+            removed_entries: 0,
+            reclaimed_bytes: 0,
+        };
+        // Une entree a la fois, en re-sondant l'espace libre avant chacune.
+        for candidate in &sessions {
+            if !below_threshold(&temp_dir) {
+                break;
+            }
+            let step =
+                run_cleanup_tier("session-scratch", std::slice::from_ref(candidate), &mut report);
+            outcome.removed_entries += step.removed_entries;
+            outcome.reclaimed_bytes = outcome.reclaimed_bytes.saturating_add(step.reclaimed_bytes);
+        }
+        report.tiers.push(outcome);
+    }
+
+    // Palier de plus basse priorite: balayage par age sur ce qui reste.
+    let age_outcome = run_age_sweep(&temp_dir, cutoff, &open_sessions, &mut report);
+    report.tiers.push(age_outcome);
+
+    let available_after = available_disk_space(&temp_dir).unwrap_or(available_before);
+    report.available_after_bytes = available_after;
+    report.freed_space_delta_bytes = available_after as i64 - available_before as i64;
+
+    Ok(report)
+}
 
-    for entry in fs::read_dir(&temp_dir)? {
+/// Recense les entrees du dossier temporaire en les pre-mesurant, en ignorant les sessions
+/// ouvertes. Les erreurs par entree sont loggees et l'entree est simplement omise.
+fn collect_temp_candidates(temp_dir: &Path, open_sessions: &[PathBuf]) -> Vec<TempCandidate> {
+    let read_dir = match fs::read_dir(temp_dir) {
+        Ok(iter) => iter,
+        Err(err) => {
+            log::error!("cleanup: unable to read {:?}: {err}", temp_dir);
+            return Vec::new();
+        }
+    };
+
+    let mut candidates = Vec::new();
+    for entry in read_dir {
         let entry = match entry {
             Ok(item) => item,
             Err(err) => {
@@ -241,6 +557,11 @@ fn cleanup_temporary_files() -> anyhow::Result<CleanupReport> {
         };
 
         let path = entry.path();
+        if open_sessions.iter().any(|dir| dir == &path) {
+            log::debug!("cleanup: session ouverte conservee {:?}", path);
+            continue;
+        }
+
         let metadata = match entry.metadata() {
             Ok(meta) => meta,
             Err(err) => {
@@ -249,41 +570,88 @@ fn cleanup_temporary_files() -> anyhow::Result<CleanupReport> {
             }
         };
 
-        // En cas d'absence d'info de modification, on prefere purger pour rester safe.
-        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
-        if modified > cutoff {
-            continue;
-        }
-
-        let size = if metadata.is_dir() {
-            match compute_dir_size(&path) {
-                Ok(value) => value,
-                Err(err) => {
-                    log::error!("cleanup: unable to measure dir {:?}: {err}", path);
-                    0
-                }
-            }
+        let is_dir = metadata.is_dir();
+        let size = if is_dir {
+            compute_dir_size(&path).unwrap_or_else(|err| {
+                log::error!("cleanup: unable to measure dir {:?}: {err}", path);
+                0
+            })
         } else {
             metadata.len()
         };
 
-        let removal_result = if metadata.is_dir() {
-            fs::remove_dir_all(&path)
-        } else {
-            fs::remove_file(&path)
-        };
+        candidates.push(TempCandidate {
+            path,
+            is_dir,
+            size,
+            // En cas d'absence d'info de modification, on traite l'entree comme tres ancienne.
+            modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+        });
+    }
 
-        match removal_result {
-            Ok(_) => {
-                report.cleaned_entries += 1;
-                report.reclaimed_bytes = report.reclaimed_bytes.saturating_add(size);
-                log::info!("cleanup: removed {:?} ({} bytes)", path, size);
-            }
-            Err(err) => log::error!("cleanup: unable to remove {:?}: {err}", path),
+    candidates
+}
+
+/// Supprime un artefact temporaire. Renvoie `true` en cas de succes.
+fn remove_candidate(candidate: &TempCandidate) -> bool {
+    let result = if candidate.is_dir {
+        fs::remove_dir_all(&candidate.path)
+    } else {
+        fs::remove_file(&candidate.path)
+    };
+    match result {
+        Ok(_) => {
+            log::info!(
+                "cleanup: removed {:?} ({} bytes)",
+                candidate.path,
+                candidate.size
+            );
+            true
+        }
+        Err(err) => {
+            log::error!("cleanup: unable to remove {:?}: {err}", candidate.path);
+            false
         }
     }
+}
 
-    Ok(report)
+/// Execute un palier: supprime ses candidats et agrege le resultat dans le rapport global.
+fn run_cleanup_tier(
+    label: &str,
+    candidates: &[TempCandidate],
+    report: &mut CleanupReport,
+) -> TierOutcome {
+    let mut outcome = TierOutcome {
+        tier: label.to_string(),
+        removed_entries: 0,
+        reclaimed_bytes: 0,
+    };
+
+    for candidate in candidates {
+        if remove_candidate(candidate) {
+            outcome.removed_entries += 1;
+            outcome.reclaimed_bytes = outcome.reclaimed_bytes.saturating_add(candidate.size);
+            report.cleaned_entries += 1;
+            report.reclaimed_bytes = report.reclaimed_bytes.saturating_add(candidate.size);
+        }
+    }
+
+    outcome
+}
+
+/// Palier de balayage par age: supprime tout ce qui reste et depasse `TEMP_RETENTION_HOURS`,
+/// en preservant toujours les sessions ouvertes. Conserve le comportement historique.
+fn run_age_sweep(
+    temp_dir: &Path,
+    cutoff: SystemTime,
+    open_sessions: &[PathBuf],
+    report: &mut CleanupReport,
+) -> TierOutcome {
+    let expired: Vec<TempCandidate> = collect_temp_candidates(temp_dir, open_sessions)
+        .into_iter()
+        .filter(|candidate| candidate.modified <= cutoff)
+        .collect();
+    run_cleanup_tier("age", &expired, report)
 }
 
 /// Mesure l'espace disque disponible sur le volume qui contient `path`. En cas d'absence
@@ -310,6 +678,7 @@ fn available_disk_space(path: &Path) -> anyhow::Result<u64> {
 /// afin d'alimenter l'UI et les logs. Cette commande pourra etre appelee au startup et a la demande.
 #[tauri::command]
 fn housekeeping() -> Result<HousekeepingStatus, String> {
+    let _span = profiling::guard("housekeeping");
     log::info!("housekeeping:start");
 
     temporary_workspace_dir()
@@ -347,6 +716,7 @@ fn housekeeping() -> Result<HousekeepingStatus, String> {
                 low_space,
                 temp_dir: temp_dir.to_string_lossy().to_string(),
                 cleanup,
+                sessions: session::footprints(),
             })
         })
 }
@@ -388,16 +758,34 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             ping,
             log_path,
-            capture_frame_stub,
+            capture_frame,
+            camera_preview,
+            camera_read_config,
+            camera_set_config,
             detect_document_stub,
             export_pdf_stub,
             runtime_info,
-            housekeeping
+            housekeeping,
+            profiling_report,
+            open_exported_file,
+            reveal_in_file_manager,
+            sandbox_info,
+            start_scan_session,
+            append_scan_page,
+            reorder_scan_pages,
+            delete_scan_pages,
+            export_scan_session,
+            close_scan_session
         ])
         .setup(|_app| {
             if let Err(err) = init_logger() {
                 eprintln!("Logger init error: {err}");
             }
+            // Armer le profilage local selon la configuration persistee (overhead nul sinon).
+            match load_app_config() {
+                Ok(config) => profiling::set_enabled(config.profiling_enabled),
+                Err(err) => log::error!("startup: profiling gate load failed: {err}"),
+            }
             // Lancer un nettoyage proactif au demarrage afin de ne pas laisser l'espace disque
             // se degrader entre deux sessions d'utilisation.
             if let Err(err) = housekeeping() {
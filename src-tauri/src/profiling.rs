@@ -0,0 +1,194 @@
+//! Auto-profilage local du pipeline capture -> detection -> export.
+//!
+//! Inspire du `SelfProfiler` de rustc : un enregistreur global au processus tient une horloge
+//! monotone et une carte `nom d'evenement -> duree cumulee + nombre d'invocations`, ainsi qu'un
+//! journal ordonne de spans `{event_name, stage, start_ns, duration_ns}`. Un garde RAII
+//! `TimingGuard` enregistre un span a sa destruction ; chaque etape du pipeline en est enrobee.
+//!
+//! Aucun reseau : le rapport est renvoye au frontend et un fichier de trace JSON est ecrit a
+//! cote de `photon.log`. L'overhead est quasi nul lorsque le profilage est desactive grace a
+//! une barriere `AtomicBool` pilotee par `AppConfig`.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use serde::Serialize;
+
+static PROFILER: OnceLock<SelfProfiler> = OnceLock::new();
+
+/// Accede a l'enregistreur global, en l'initialisant au premier appel.
+fn profiler() -> &'static SelfProfiler {
+    PROFILER.get_or_init(SelfProfiler::new)
+}
+
+/// Active ou desactive le profilage. Pilote depuis `AppConfig` au demarrage ; lorsque la
+/// barriere est a `false`, les gardes ne prennent aucun verrou et n'enregistrent rien.
+pub fn set_enabled(enabled: bool) {
+    profiler().enabled.store(enabled, Ordering::Relaxed);
+    log::debug!("profiling: gate => {enabled}");
+}
+
+/// Ouvre un span pour l'etape `event_name`. Le span est enregistre a la destruction du garde.
+pub fn guard(event_name: &'static str) -> TimingGuard {
+    TimingGuard::new(event_name)
+}
+
+/// Span enregistre dans le journal ordonne.
+#[derive(Serialize, Clone)]
+struct RecordedSpan {
+    event_name: String,
+    stage: String,
+    start_ns: u128,
+    duration_ns: u128,
+}
+
+/// Cumul par etape : duree totale et nombre d'invocations.
+#[derive(Default)]
+struct StageAccumulator {
+    total_ns: u128,
+    count: u64,
+}
+
+#[derive(Default)]
+struct ProfilerState {
+    totals: BTreeMap<String, StageAccumulator>,
+    events: Vec<RecordedSpan>,
+}
+
+struct SelfProfiler {
+    enabled: AtomicBool,
+    base: Instant,
+    state: Mutex<ProfilerState>,
+}
+
+impl SelfProfiler {
+    fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            base: Instant::now(),
+            state: Mutex::new(ProfilerState::default()),
+        }
+    }
+
+    fn record(&self, span: RecordedSpan) {
+        let mut state = match self.state.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let entry = state.totals.entry(span.event_name.clone()).or_default();
+        entry.total_ns = entry.total_ns.saturating_add(span.duration_ns);
+        entry.count += 1;
+        state.events.push(span);
+    }
+}
+
+/// Garde RAII : mesure la duree de vie de l'objet et enregistre le span a sa destruction.
+pub struct TimingGuard {
+    event_name: &'static str,
+    start_ns: u128,
+}
+
+impl TimingGuard {
+    fn new(event_name: &'static str) -> Self {
+        // On lit l'horloge meme desactive : `Instant::elapsed` est quasi gratuit, ce qui
+        // evite une branche supplementaire sur le chemin chaud.
+        let start_ns = profiler().base.elapsed().as_nanos();
+        Self {
+            event_name,
+            start_ns,
+        }
+    }
+}
+
+impl Drop for TimingGuard {
+    fn drop(&mut self) {
+        let profiler = profiler();
+        if !profiler.enabled.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let end_ns = profiler.base.elapsed().as_nanos();
+        // Meme garde d'overflow que `cleanup_temporary_files` sur son cutoff : si l'horloge
+        // recule, on journalise et on retombe sur une duree nulle plutot que de paniquer.
+        let duration_ns = match end_ns.checked_sub(self.start_ns) {
+            Some(value) => value,
+            None => {
+                log::error!(
+                    "profiling: clock overflow for {} (start_ns={}, end_ns={})",
+                    self.event_name,
+                    self.start_ns,
+                    end_ns
+                );
+                0
+            }
+        };
+
+        profiler.record(RecordedSpan {
+            event_name: self.event_name.to_string(),
+            stage: format!("{:?}", std::thread::current().id()),
+            start_ns: self.start_ns,
+            duration_ns,
+        });
+    }
+}
+
+/// Totaux agreges par etape, exposes au frontend.
+#[derive(Serialize)]
+pub struct StageReport {
+    pub event_name: String,
+    pub total_ns: u128,
+    pub count: u64,
+    pub average_ns: u128,
+}
+
+/// Rapport complet renvoye par `profiling_report` : agregats par etape et chemin de la trace.
+#[derive(Serialize)]
+pub struct ProfilingReport {
+    pub enabled: bool,
+    pub stages: Vec<StageReport>,
+    pub trace_path: Option<String>,
+}
+
+/// Agrege l'etat courant, ecrit le journal ordonne dans un fichier de trace JSON a cote de
+/// `photon.log`, et renvoie les totaux/moyennes par etape.
+pub fn report(trace_path: std::path::PathBuf) -> anyhow::Result<ProfilingReport> {
+    let profiler = profiler();
+    let enabled = profiler.enabled.load(Ordering::Relaxed);
+
+    let (stages, events) = {
+        let state = match profiler.state.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let stages = state
+            .totals
+            .iter()
+            .map(|(event_name, acc)| StageReport {
+                event_name: event_name.clone(),
+                total_ns: acc.total_ns,
+                count: acc.count,
+                average_ns: acc
+                    .total_ns
+                    .checked_div(acc.count as u128)
+                    .unwrap_or_default(),
+            })
+            .collect();
+        (stages, state.events.clone())
+    };
+
+    let trace_path = match std::fs::write(&trace_path, serde_json::to_vec_pretty(&events)?) {
+        Ok(_) => Some(trace_path.to_string_lossy().to_string()),
+        Err(err) => {
+            log::error!("profiling: unable to flush trace {:?}: {err}", trace_path);
+            None
+        }
+    };
+
+    Ok(ProfilingReport {
+        enabled,
+        stages,
+        trace_path,
+    })
+}
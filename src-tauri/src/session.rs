@@ -0,0 +1,287 @@
+//! Sous-systeme de session de numerisation multi-pages alimentant un unique `export_pdf`.
+//!
+//! Une session regroupe un ensemble ordonne de pages capturees ou importees. Les buffers
+//! volumineux sont deverses sur disque dans un sous-dossier dedie de `temporary_workspace_dir()`
+//! afin de ne pas saturer la memoire. A l'image des jobs filesystem qui acceptent plusieurs
+//! sources a la fois, les operations de reordonnancement/suppression prennent une liste d'ids de
+//! pages pour que l'UI puisse deplacer ou retirer plusieurs pages en un seul appel.
+//!
+//! Les sessions ouvertes sont suivies globalement afin que `housekeeping` puisse reporter leur
+//! empreinte disque et que `cleanup_temporary_files` n'efface jamais le scratch d'une session
+//! encore ouverte.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use serde::Serialize;
+
+use crate::imaging::{self, ColorPipeline};
+
+/// Format de sortie d'une session : un PDF multi-pages ou une archive ZIP d'images.
+#[derive(Clone, Copy)]
+pub enum ExportFormat {
+    Pdf,
+    Zip,
+}
+
+/// Une page de la session, materialisee par un fichier dans le dossier de scratch.
+struct Page {
+    id: String,
+    path: PathBuf,
+}
+
+/// Session de numerisation en cours : un dossier de scratch et une liste ordonnee de pages.
+struct Session {
+    dir: PathBuf,
+    pages: Vec<Page>,
+}
+
+/// Empreinte disque d'une session, exposee a l'UI via `HousekeepingStatus`.
+#[derive(Serialize)]
+pub struct SessionFootprint {
+    pub session_id: String,
+    pub page_count: usize,
+    pub bytes: u64,
+}
+
+static SESSIONS: OnceLock<Mutex<HashMap<String, Session>>> = OnceLock::new();
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+fn sessions() -> &'static Mutex<HashMap<String, Session>> {
+    SESSIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn lock() -> std::sync::MutexGuard<'static, HashMap<String, Session>> {
+    match sessions().lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Genere un identifiant unique au processus (pid + compteur monotone), sans dependance externe.
+fn fresh_id(prefix: &str) -> String {
+    let counter = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    format!("{prefix}-{}-{counter}", std::process::id())
+}
+
+/// Demarre une nouvelle session et cree son sous-dossier de scratch.
+pub fn start_session(workspace: &Path) -> anyhow::Result<String> {
+    let id = fresh_id("session");
+    let dir = workspace.join(&id);
+    std::fs::create_dir_all(&dir)?;
+
+    lock().insert(
+        id.clone(),
+        Session {
+            dir,
+            pages: Vec::new(),
+        },
+    );
+    log::info!("session: {id} demarree");
+    Ok(id)
+}
+
+/// Ajoute une page a la session en deversant le buffer sur disque. Renvoie l'id de la page.
+pub fn append_page(session_id: &str, bytes: &[u8], extension: &str) -> anyhow::Result<String> {
+    let mut guard = lock();
+    let session = guard
+        .get_mut(session_id)
+        .ok_or_else(|| anyhow::anyhow!("session inconnue: {session_id}"))?;
+
+    let page_id = fresh_id("page");
+    let ext = extension.trim_start_matches('.').to_ascii_lowercase();
+    let path = session.dir.join(format!("{page_id}.{ext}"));
+    std::fs::write(&path, bytes)?;
+
+    session.pages.push(Page {
+        id: page_id.clone(),
+        path,
+    });
+    log::info!(
+        "session {session_id}: page {page_id} ajoutee ({} octets)",
+        bytes.len()
+    );
+    Ok(page_id)
+}
+
+/// Reordonne les pages selon la liste d'ids fournie. Toute page absente de la liste est
+/// conservee a la fin dans son ordre courant, pour ne jamais perdre de contenu.
+pub fn reorder_pages(session_id: &str, ordered_ids: &[String]) -> anyhow::Result<()> {
+    let mut guard = lock();
+    let session = guard
+        .get_mut(session_id)
+        .ok_or_else(|| anyhow::anyhow!("session inconnue: {session_id}"))?;
+
+    let mut remaining: Vec<Page> = std::mem::take(&mut session.pages);
+    let mut reordered = Vec::with_capacity(remaining.len());
+
+    for id in ordered_ids {
+        if let Some(pos) = remaining.iter().position(|page| &page.id == id) {
+            reordered.push(remaining.remove(pos));
+        }
+    }
+    reordered.append(&mut remaining);
+    session.pages = reordered;
+    Ok(())
+}
+
+/// Supprime plusieurs pages en un seul appel (fichiers + entrees).
+pub fn delete_pages(session_id: &str, page_ids: &[String]) -> anyhow::Result<()> {
+    let mut guard = lock();
+    let session = guard
+        .get_mut(session_id)
+        .ok_or_else(|| anyhow::anyhow!("session inconnue: {session_id}"))?;
+
+    session.pages.retain(|page| {
+        if page_ids.contains(&page.id) {
+            if let Err(err) = std::fs::remove_file(&page.path) {
+                log::error!("session {session_id}: suppression {:?} impossible: {err}", page.path);
+            }
+            false
+        } else {
+            true
+        }
+    });
+    Ok(())
+}
+
+/// Exporte l'ensemble ordonne des pages vers un PDF multi-pages ou un ZIP d'images.
+/// Le pipeline couleur du profil actif est applique avant l'assemblage.
+pub fn export_session(
+    session_id: &str,
+    output: &Path,
+    format: ExportFormat,
+    pipeline: ColorPipeline,
+) -> anyhow::Result<()> {
+    let guard = lock();
+    let session = guard
+        .get(session_id)
+        .ok_or_else(|| anyhow::anyhow!("session inconnue: {session_id}"))?;
+
+    if session.pages.is_empty() {
+        anyhow::bail!("session {session_id} vide, rien a exporter");
+    }
+
+    match format {
+        ExportFormat::Pdf => export_pdf(&session.pages, output, pipeline),
+        ExportFormat::Zip => export_zip(&session.pages, output, pipeline),
+    }
+}
+
+fn export_pdf(pages: &[Page], output: &Path, pipeline: ColorPipeline) -> anyhow::Result<()> {
+    use printpdf::{ImageTransform, Mm, PdfDocument};
+
+    // Page A4 portrait par defaut ; chaque image est posee a 300 dpi.
+    const A4_WIDTH_MM: f64 = 210.0;
+    const A4_HEIGHT_MM: f64 = 297.0;
+
+    let (doc, first_page, first_layer) =
+        PdfDocument::new("Photon", Mm(A4_WIDTH_MM), Mm(A4_HEIGHT_MM), "page-1");
+
+    const DPI: f64 = 300.0;
+    const MM_PER_INCH: f64 = 25.4;
+
+    for (index, page) in pages.iter().enumerate() {
+        let decoded = imaging::decode_and_process(&page.path, pipeline)?;
+        let rgb = decoded.to_rgb8();
+        let (width_px, height_px) = rgb.dimensions();
+        let dynamic = printpdf::image::DynamicImage::ImageRgb8(rgb);
+        let image = printpdf::Image::from_dynamic_image(&dynamic);
+
+        // printpdf dimensionne l'image a `pixels / dpi`. Une frame pleine resolution deborderait
+        // largement l'A4 : on calcule un facteur d'echelle pour qu'elle tienne dans la page (sans
+        // jamais agrandir au-dela de 1:1) puis on la centre.
+        let natural_width_mm = width_px as f64 / DPI * MM_PER_INCH;
+        let natural_height_mm = height_px as f64 / DPI * MM_PER_INCH;
+        let scale = (A4_WIDTH_MM / natural_width_mm)
+            .min(A4_HEIGHT_MM / natural_height_mm)
+            .min(1.0);
+        let draw_width_mm = natural_width_mm * scale;
+        let draw_height_mm = natural_height_mm * scale;
+        let translate_x = Mm((A4_WIDTH_MM - draw_width_mm) / 2.0);
+        let translate_y = Mm((A4_HEIGHT_MM - draw_height_mm) / 2.0);
+
+        // La premiere page existe deja ; les suivantes sont ajoutees a la demande.
+        let layer = if index == 0 {
+            doc.get_page(first_page).get_layer(first_layer)
+        } else {
+            let (next_page, next_layer) =
+                doc.add_page(Mm(A4_WIDTH_MM), Mm(A4_HEIGHT_MM), format!("page-{}", index + 1));
+            doc.get_page(next_page).get_layer(next_layer)
+        };
+
+        image.add_to_layer(
+            layer,
+            ImageTransform {
+                translate_x: Some(translate_x),
+                translate_y: Some(translate_y),
+                scale_x: Some(scale),
+                scale_y: Some(scale),
+                dpi: Some(DPI),
+                ..Default::default()
+            },
+        );
+    }
+
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(output)?);
+    doc.save(&mut writer)?;
+    log::info!("session export PDF: {:?} ({} pages)", output, pages.len());
+    Ok(())
+}
+
+fn export_zip(pages: &[Page], output: &Path, pipeline: ColorPipeline) -> anyhow::Result<()> {
+    use std::io::Write;
+
+    let file = std::fs::File::create(output)?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options: zip::write::FileOptions<()> =
+        zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+
+    for (index, page) in pages.iter().enumerate() {
+        let decoded = imaging::decode_and_process(&page.path, pipeline)?;
+        let mut buffer = std::io::Cursor::new(Vec::new());
+        decoded.write_to(&mut buffer, image::ImageFormat::Png)?;
+
+        zip.start_file(format!("page-{:03}.png", index + 1), options)?;
+        zip.write_all(&buffer.into_inner())?;
+    }
+
+    zip.finish()?;
+    log::info!("session export ZIP: {:?} ({} pages)", output, pages.len());
+    Ok(())
+}
+
+/// Ferme une session et supprime son dossier de scratch.
+pub fn close_session(session_id: &str) -> anyhow::Result<()> {
+    let session = lock().remove(session_id);
+    if let Some(session) = session {
+        if let Err(err) = std::fs::remove_dir_all(&session.dir) {
+            log::error!("session {session_id}: nettoyage {:?} impossible: {err}", session.dir);
+        }
+        log::info!("session {session_id} fermee");
+    }
+    Ok(())
+}
+
+/// Renvoie le dossier de scratch de chaque session encore ouverte, pour que le nettoyage
+/// par age n'y touche pas.
+pub fn open_session_dirs() -> Vec<PathBuf> {
+    lock().values().map(|s| s.dir.clone()).collect()
+}
+
+/// Empreinte disque par session, pour alimenter `HousekeepingStatus`.
+pub fn footprints() -> Vec<SessionFootprint> {
+    lock()
+        .iter()
+        .map(|(id, session)| {
+            let bytes = crate::compute_dir_size(&session.dir).unwrap_or(0);
+            SessionFootprint {
+                session_id: id.clone(),
+                page_count: session.pages.len(),
+                bytes,
+            }
+        })
+        .collect()
+}
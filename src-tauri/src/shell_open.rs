@@ -0,0 +1,226 @@
+//! Ouverture de fichiers et revelation dans le gestionnaire de fichiers, cross-plateforme.
+//!
+//! Apres `export_pdf`, l'utilisateur veut pouvoir ouvrir le fichier produit ou le localiser dans
+//! son explorateur. On delegue au handler par defaut de l'OS (shell Win32, `open -R` sur macOS,
+//! `xdg-open` / `ShowItems` D-Bus sur Linux).
+//!
+//! Point critique sous Linux : quand Photon est lui-meme empaquete (Flatpak/Snap/AppImage), son
+//! environnement contient des chemins de bibliotheques injectes qui casseraient les visionneuses
+//! externes. On normalise donc l'environnement du processus enfant avant de le lancer.
+
+use std::path::Path;
+use std::process::Command;
+
+/// Ouvre `path` avec le handler par defaut de l'OS.
+pub fn open_exported_file(path: &Path) -> anyhow::Result<()> {
+    if !path.exists() {
+        anyhow::bail!("fichier introuvable: {:?}", path);
+    }
+
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", "start", ""]).arg(path);
+        cmd
+    };
+
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut cmd = Command::new("open");
+        cmd.arg(path);
+        cmd
+    };
+
+    #[cfg(target_os = "linux")]
+    let mut command = {
+        let mut cmd = Command::new("xdg-open");
+        cmd.arg(path);
+        normalize_child_env(&mut cmd);
+        cmd
+    };
+
+    spawn(&mut command, "open_exported_file")
+}
+
+/// Revele `path` dans le gestionnaire de fichiers, en selectionnant l'entree si possible.
+pub fn reveal_in_file_manager(path: &Path) -> anyhow::Result<()> {
+    if !path.exists() {
+        anyhow::bail!("fichier introuvable: {:?}", path);
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let mut command = Command::new("explorer");
+        command.arg(format!("/select,{}", path.display()));
+        // `explorer /select` renvoie un code de sortie non nul meme en cas de succes.
+        spawn_detached(&mut command, "reveal_in_file_manager")
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let mut command = Command::new("open");
+        command.arg("-R").arg(path);
+        spawn(&mut command, "reveal_in_file_manager")
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        // Chemin privilegie : l'interface D-Bus org.freedesktop.FileManager1 selectionne l'item.
+        let uri = format!("file://{}", path.display());
+        let mut dbus = Command::new("dbus-send");
+        dbus.args([
+            "--session",
+            "--dest=org.freedesktop.FileManager1",
+            "--type=method_call",
+            "/org/freedesktop/FileManager1",
+            "org.freedesktop.FileManager1.ShowItems",
+            &format!("array:string:{uri}"),
+            "string:",
+        ]);
+        normalize_child_env(&mut dbus);
+
+        // `spawn` accepte un code de sortie non nul : on teste ici le statut de dbus-send.
+        if dbus.status().map(|status| status.success()).unwrap_or(false) {
+            return Ok(());
+        }
+
+        // Repli : ouvrir le dossier parent avec xdg-open.
+        let parent = path.parent().unwrap_or(path);
+        log::warn!("reveal_in_file_manager: D-Bus indisponible, repli xdg-open sur {parent:?}");
+        let mut fallback = Command::new("xdg-open");
+        fallback.arg(parent);
+        normalize_child_env(&mut fallback);
+        spawn(&mut fallback, "reveal_in_file_manager")
+    }
+}
+
+/// Lance la commande et attend son code de sortie, en journalisant les echecs.
+fn spawn(command: &mut Command, context: &str) -> anyhow::Result<()> {
+    let status = command.status().map_err(|err| {
+        log::error!("{context}: lancement impossible: {err}");
+        anyhow::anyhow!("aucun handler disponible: {err}")
+    })?;
+    if !status.success() {
+        log::warn!("{context}: handler termine avec le statut {status}");
+    }
+    Ok(())
+}
+
+/// Variante ne verifiant pas le code de sortie (certains handlers en renvoient un non nul).
+#[cfg(target_os = "windows")]
+fn spawn_detached(command: &mut Command, context: &str) -> anyhow::Result<()> {
+    command.spawn().map_err(|err| {
+        log::error!("{context}: lancement impossible: {err}");
+        anyhow::anyhow!("aucun handler disponible: {err}")
+    })?;
+    Ok(())
+}
+
+/// Indique si Photon s'execute dans un Flatpak.
+pub fn running_in_flatpak() -> bool {
+    std::env::var_os("FLATPAK_ID").is_some() || Path::new("/.flatpak-info").exists()
+}
+
+/// Indique si Photon s'execute dans un Snap.
+pub fn running_in_snap() -> bool {
+    std::env::var_os("SNAP").is_some()
+}
+
+/// Indique si Photon s'execute depuis une AppImage.
+pub fn running_in_appimage() -> bool {
+    std::env::var_os("APPIMAGE").is_some() || std::env::var_os("APPDIR").is_some()
+}
+
+/// Normalise l'environnement du processus enfant sous Linux pour ne pas propager les chemins de
+/// bibliotheques injectes par le bac a sable. Concretement : on reconstruit `PATH` et les
+/// variables XDG a partir des entrees systeme, on retire de `LD_LIBRARY_PATH` les entrees pointant
+/// dans le bundle, on supprime les `GST_PLUGIN_*`, et on de-duplique les listes en preferant les
+/// entrees systeme.
+#[cfg(target_os = "linux")]
+fn normalize_child_env(command: &mut Command) {
+    const SYSTEM_PATH: &[&str] = &[
+        "/usr/local/bin",
+        "/usr/bin",
+        "/bin",
+        "/usr/local/sbin",
+        "/usr/sbin",
+        "/sbin",
+    ];
+    const SYSTEM_XDG_DATA_DIRS: &[&str] = &["/usr/local/share", "/usr/share"];
+    const SYSTEM_XDG_CONFIG_DIRS: &[&str] = &["/etc/xdg"];
+
+    // Fragments de chemins trahissant une origine "bundle" a ecarter.
+    const BUNDLE_MARKERS: &[&str] = &["/app/", "/snap/", ".mount_", "/.flatpak", "squashfs-root"];
+
+    let is_bundled = |entry: &str| BUNDLE_MARKERS.iter().any(|marker| entry.contains(marker));
+
+    // PATH : entrees systeme d'abord, puis le reste de l'utilisateur hors bundle, sans doublon.
+    let current_path = std::env::var("PATH").unwrap_or_default();
+    let rebuilt_path = dedup_preferring_system(SYSTEM_PATH, &current_path, is_bundled);
+    command.env("PATH", rebuilt_path);
+
+    // XDG_DATA_DIRS : meme logique, pour que les .desktop systeme restent visibles.
+    let current_data_dirs = std::env::var("XDG_DATA_DIRS").unwrap_or_default();
+    let rebuilt_data_dirs =
+        dedup_preferring_system(SYSTEM_XDG_DATA_DIRS, &current_data_dirs, is_bundled);
+    command.env("XDG_DATA_DIRS", rebuilt_data_dirs);
+
+    // XDG_CONFIG_DIRS : idem, avec /etc/xdg en tete.
+    let current_config_dirs = std::env::var("XDG_CONFIG_DIRS").unwrap_or_default();
+    let rebuilt_config_dirs =
+        dedup_preferring_system(SYSTEM_XDG_CONFIG_DIRS, &current_config_dirs, is_bundled);
+    command.env("XDG_CONFIG_DIRS", rebuilt_config_dirs);
+
+    // LD_LIBRARY_PATH : on ne garde que les entrees hors bundle ; vide => on retire la variable.
+    match std::env::var("LD_LIBRARY_PATH") {
+        Ok(value) => {
+            let kept: Vec<&str> = value
+                .split(':')
+                .filter(|entry| !entry.is_empty() && !is_bundled(entry))
+                .collect();
+            if kept.is_empty() {
+                command.env_remove("LD_LIBRARY_PATH");
+            } else {
+                command.env("LD_LIBRARY_PATH", kept.join(":"));
+            }
+        }
+        Err(_) => command.env_remove("LD_LIBRARY_PATH"),
+    };
+
+    // Les chemins de plugins GStreamer pointent vers le bundle : on les retire systematiquement.
+    for key in ["GST_PLUGIN_PATH", "GST_PLUGIN_PATH_1_0", "GST_PLUGIN_SYSTEM_PATH"] {
+        command.env_remove(key);
+    }
+}
+
+/// Reconstruit une liste de chemins `:`-separee : entrees systeme en tete, puis les entrees
+/// courantes hors bundle, sans doublon et en preservant l'ordre.
+#[cfg(target_os = "linux")]
+fn dedup_preferring_system(
+    system: &[&str],
+    current: &str,
+    is_bundled: impl Fn(&str) -> bool,
+) -> String {
+    use std::collections::HashSet;
+
+    let mut seen = HashSet::new();
+    let mut result = Vec::new();
+
+    for entry in system
+        .iter()
+        .map(|s| s.to_string())
+        .chain(current.split(':').filter_map(|entry| {
+            if entry.is_empty() || is_bundled(entry) {
+                None
+            } else {
+                Some(entry.to_string())
+            }
+        }))
+    {
+        if seen.insert(entry.clone()) {
+            result.push(entry);
+        }
+    }
+
+    result.join(":")
+}